@@ -12,14 +12,22 @@ use super::super::circuit::utils::be_bit_vector_into_bytes;
 use super::super::circuit::baby_plasma::{Update, Transaction, TransactionWitness};
 
 use sapling_crypto::alt_babyjubjub::{AltJubjubBn256};
+use sapling_crypto::jubjub::{edwards, JubjubEngine, Unknown, FixedGenerators};
+use sapling_crypto::eddsa::{PublicKey, Signature};
 
 use pairing::bn256::Bn256;
-use pairing::bn256::Fr;
-use bellman::groth16::{Proof, Parameters, create_random_proof, verify_proof, prepare_verifying_key};
+use pairing::bn256::{Fr, Fq, G1Affine, G2Affine};
+use pairing::CurveAffine;
+use bellman::groth16::{Proof, Parameters, VerifyingKey, create_random_proof, verify_proof, prepare_verifying_key};
 
 use crypto::sha2::Sha256;
 use crypto::digest::Digest;
 
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender, Receiver, TryRecvError};
+use std::thread;
+use std::io::{self, Read, Write};
+
 #[derive(Debug)]
 pub enum BabyProverErr {
     Unknown,
@@ -27,6 +35,10 @@ pub enum BabyProverErr {
     InvalidFeeEncoding,
     InvalidSender,
     InvalidRecipient,
+    InvalidSignature,
+    TransactionExpired,
+    BatchOverflow,
+    MissingPaddingAccount,
     IoError(std::io::Error)
 }
 
@@ -38,6 +50,10 @@ impl Error for BabyProverErr {
             BabyProverErr::InvalidFeeEncoding => "transfer fee is malformed or too large",
             BabyProverErr::InvalidSender => "sender account is unknown",
             BabyProverErr::InvalidRecipient => "recipient account is unknown",
+            BabyProverErr::InvalidSignature => "transaction signature does not verify against the sender's public key",
+            BabyProverErr::TransactionExpired => "transaction's good_until_block has already passed",
+            BabyProverErr::BatchOverflow => "block has more transactions than the prover's batch_size",
+            BabyProverErr::MissingPaddingAccount => "reserved padding account is not present in the accounts tree",
             BabyProverErr::IoError(_) => "encountered an I/O error",
         }
     }
@@ -54,12 +70,328 @@ impl fmt::Display for BabyProverErr {
     }
 }
 
+// An incoming transaction together with the sender's on-chain public key and the block it is
+// being applied into. Nothing here has been checked yet, following OpenEthereum's split between
+// unverified and signed transactions; here `verify` turns this into a `VerifiedTransaction`.
+pub struct UnverifiedTransaction<E: JubjubEngine> {
+    pub from: E::Fr,
+    pub to: E::Fr,
+    pub amount: E::Fr,
+    pub fee: E::Fr,
+    pub nonce: E::Fr,
+    pub good_until_block: E::Fr,
+    pub signature: Signature<E>,
+    sender_pub_x: E::Fr,
+    sender_pub_y: E::Fr,
+    block_number: u32,
+}
+
+// A transaction whose signature has been checked against the sender leaf's public key and whose
+// `good_until_block` has not yet passed. `apply` passes every incoming transaction through
+// `verify` and builds witnesses only from this type, so a forged or expired transfer can never
+// reach the witness. `apply_and_prove` itself still takes a raw `Block`.
+pub struct VerifiedTransaction<E: JubjubEngine> {
+    unverified: UnverifiedTransaction<E>,
+}
+
+impl<E: JubjubEngine> std::ops::Deref for VerifiedTransaction<E> {
+    type Target = UnverifiedTransaction<E>;
+
+    fn deref(&self) -> &UnverifiedTransaction<E> {
+        &self.unverified
+    }
+}
+
+impl UnverifiedTransaction<Bn256> {
+    pub fn new(
+        from: Fr,
+        to: Fr,
+        amount: Fr,
+        fee: Fr,
+        nonce: Fr,
+        good_until_block: Fr,
+        signature: Signature<Bn256>,
+        sender_pub_x: Fr,
+        sender_pub_y: Fr,
+        block_number: u32,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            amount,
+            fee,
+            nonce,
+            good_until_block,
+            signature,
+            sender_pub_x,
+            sender_pub_y,
+            block_number,
+        }
+    }
+
+    // The exact bit layout the client signs over: from, to, amount, fee, nonce, good_until_block,
+    // each as the BE bits of its field element representation, one after another.
+    fn signed_message_bits(&self) -> Vec<bool> {
+        let mut bits = vec![];
+        bits.extend(BitIterator::new(self.from.into_repr()));
+        bits.extend(BitIterator::new(self.to.into_repr()));
+        bits.extend(BitIterator::new(self.amount.into_repr()));
+        bits.extend(BitIterator::new(self.fee.into_repr()));
+        bits.extend(BitIterator::new(self.nonce.into_repr()));
+        bits.extend(BitIterator::new(self.good_until_block.into_repr()));
+        bits
+    }
+
+    pub fn verify(self, params: &AltJubjubBn256) -> Result<VerifiedTransaction<Bn256>, BabyProverErr> {
+        if field_element_to_u32(self.good_until_block) < self.block_number {
+            return Err(BabyProverErr::TransactionExpired);
+        }
+
+        let message = be_bit_vector_into_bytes(&self.signed_message_bits());
+
+        let sender_point = edwards::Point::<Bn256, Unknown>::from_xy_unchecked(self.sender_pub_x, self.sender_pub_y);
+        let public_key = PublicKey(sender_point);
+
+        let signature_is_valid = public_key.verify_for_raw_message(
+            &message,
+            &self.signature,
+            FixedGenerators::SpendingKeyGenerator,
+            params,
+            message.len(),
+        );
+
+        if !signature_is_valid {
+            return Err(BabyProverErr::InvalidSignature);
+        }
+
+        Ok(VerifiedTransaction { unverified: self })
+    }
+}
+
 pub struct BabyProver {
     batch_size: usize,
     accounts_tree: balance_tree::BabyBalanceTree,
     parameters: BabyParameters,
 }
 
+// Everything `prove_bundle` needs to produce a proof for one block, with no further access to
+// the accounts tree. Built sequentially by `apply`, then handed off for proving — possibly after
+// a round trip through `write`/`read`, so a lightweight node that only holds the state can ship
+// this to a dedicated proving machine without either side replaying the balance tree. Modeled on
+// a PSBT: a self-contained, partially-completed artifact passed between parties.
+pub struct BlockWitness {
+    pub old_root: Fr,
+    pub new_root: Fr,
+    pub public_data_commitment: Fr,
+    pub block_number: Fr,
+    pub total_fee: Fr,
+    pub witnesses: Vec<Option<(Transaction<Bn256>, TransactionWitness<Bn256>)>>,
+}
+
+fn write_field_element_be<F: PrimeField, W: Write>(el: &F, writer: &mut W) -> io::Result<()> {
+    el.into_repr().write_be(writer)
+}
+
+fn read_field_element_be<F: PrimeField, R: Read>(reader: &mut R) -> io::Result<F> {
+    let mut repr = F::zero().into_repr();
+    repr.read_be(reader)?;
+    F::from_repr(repr).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid field element encoding"))
+}
+
+fn write_optional_field_element<F: PrimeField, W: Write>(el: &Option<F>, writer: &mut W) -> io::Result<()> {
+    match el {
+        Some(el) => {
+            writer.write_all(&[1])?;
+            write_field_element_be(el, writer)
+        },
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn read_optional_field_element<F: PrimeField, R: Read>(reader: &mut R) -> io::Result<Option<F>> {
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    if tag[0] == 0 {
+        return Ok(None);
+    }
+    Ok(Some(read_field_element_be(reader)?))
+}
+
+fn write_auth_path<W: Write>(path: &[Option<(Fr, bool)>], writer: &mut W) -> io::Result<()> {
+    writer.write_all(&(path.len() as u32).to_be_bytes())?;
+    for entry in path {
+        match entry {
+            Some((el, direction)) => {
+                writer.write_all(&[1])?;
+                write_field_element_be(el, writer)?;
+                writer.write_all(&[*direction as u8])?;
+            },
+            None => writer.write_all(&[0])?,
+        }
+    }
+    Ok(())
+}
+
+fn read_auth_path<R: Read>(reader: &mut R) -> io::Result<Vec<Option<(Fr, bool)>>> {
+    let mut len_bytes = [0u8; 4];
+    reader.read_exact(&mut len_bytes)?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+
+    if len > *plasma_constants::BALANCE_TREE_DEPTH as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "auth path is deeper than the balance tree"));
+    }
+
+    let mut path = Vec::with_capacity(len);
+    for _ in 0..len {
+        let mut tag = [0u8; 1];
+        reader.read_exact(&mut tag)?;
+        if tag[0] == 0 {
+            path.push(None);
+            continue;
+        }
+        let el = read_field_element_be(reader)?;
+        let mut direction = [0u8; 1];
+        reader.read_exact(&mut direction)?;
+        path.push(Some((el, direction[0] != 0)));
+    }
+    Ok(path)
+}
+
+fn write_optional_signature<W: Write>(signature: &Option<Signature<Bn256>>, writer: &mut W) -> io::Result<()> {
+    match signature {
+        Some(signature) => {
+            writer.write_all(&[1])?;
+            signature.write(writer)
+        },
+        None => writer.write_all(&[0]),
+    }
+}
+
+fn read_optional_signature<R: Read>(reader: &mut R, params: &AltJubjubBn256) -> io::Result<Option<Signature<Bn256>>> {
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    if tag[0] == 0 {
+        return Ok(None);
+    }
+    Ok(Some(Signature::read(reader, params)?))
+}
+
+fn write_transaction<W: Write>(tx: &Transaction<Bn256>, writer: &mut W) -> io::Result<()> {
+    write_optional_field_element(&tx.from, writer)?;
+    write_optional_field_element(&tx.to, writer)?;
+    write_optional_field_element(&tx.amount, writer)?;
+    write_optional_field_element(&tx.fee, writer)?;
+    write_optional_field_element(&tx.nonce, writer)?;
+    write_optional_field_element(&tx.good_until_block, writer)?;
+    write_optional_signature(&tx.signature, writer)
+}
+
+fn read_transaction<R: Read>(reader: &mut R, params: &AltJubjubBn256) -> io::Result<Transaction<Bn256>> {
+    Ok(Transaction {
+        from: read_optional_field_element(reader)?,
+        to: read_optional_field_element(reader)?,
+        amount: read_optional_field_element(reader)?,
+        fee: read_optional_field_element(reader)?,
+        nonce: read_optional_field_element(reader)?,
+        good_until_block: read_optional_field_element(reader)?,
+        signature: read_optional_signature(reader, params)?,
+    })
+}
+
+fn write_transaction_witness<W: Write>(witness: &TransactionWitness<Bn256>, writer: &mut W) -> io::Result<()> {
+    write_auth_path(&witness.auth_path_from, writer)?;
+    write_optional_field_element(&witness.balance_from, writer)?;
+    write_optional_field_element(&witness.nonce_from, writer)?;
+    write_optional_field_element(&witness.pub_x_from, writer)?;
+    write_optional_field_element(&witness.pub_y_from, writer)?;
+    write_auth_path(&witness.auth_path_to, writer)?;
+    write_optional_field_element(&witness.balance_to, writer)?;
+    write_optional_field_element(&witness.nonce_to, writer)?;
+    write_optional_field_element(&witness.pub_x_to, writer)?;
+    write_optional_field_element(&witness.pub_y_to, writer)
+}
+
+fn read_transaction_witness<R: Read>(reader: &mut R) -> io::Result<TransactionWitness<Bn256>> {
+    Ok(TransactionWitness {
+        auth_path_from: read_auth_path(reader)?,
+        balance_from: read_optional_field_element(reader)?,
+        nonce_from: read_optional_field_element(reader)?,
+        pub_x_from: read_optional_field_element(reader)?,
+        pub_y_from: read_optional_field_element(reader)?,
+        auth_path_to: read_auth_path(reader)?,
+        balance_to: read_optional_field_element(reader)?,
+        nonce_to: read_optional_field_element(reader)?,
+        pub_x_to: read_optional_field_element(reader)?,
+        pub_y_to: read_optional_field_element(reader)?,
+    })
+}
+
+impl BlockWitness {
+    // Mirrors `Parameters::read`'s style: every field element as a fixed-width BE encoding, with
+    // a presence byte ahead of each `Option` so the bundle round-trips exactly.
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        write_field_element_be(&self.old_root, &mut writer)?;
+        write_field_element_be(&self.new_root, &mut writer)?;
+        write_field_element_be(&self.public_data_commitment, &mut writer)?;
+        write_field_element_be(&self.block_number, &mut writer)?;
+        write_field_element_be(&self.total_fee, &mut writer)?;
+
+        writer.write_all(&(self.witnesses.len() as u32).to_be_bytes())?;
+
+        for entry in &self.witnesses {
+            match entry {
+                Some((tx, witness)) => {
+                    writer.write_all(&[1])?;
+                    write_transaction(tx, &mut writer)?;
+                    write_transaction_witness(witness, &mut writer)?;
+                },
+                None => writer.write_all(&[0])?,
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R, params: &AltJubjubBn256) -> io::Result<Self> {
+        let old_root = read_field_element_be(&mut reader)?;
+        let new_root = read_field_element_be(&mut reader)?;
+        let public_data_commitment = read_field_element_be(&mut reader)?;
+        let block_number = read_field_element_be(&mut reader)?;
+        let total_fee = read_field_element_be(&mut reader)?;
+
+        let mut len_bytes = [0u8; 4];
+        reader.read_exact(&mut len_bytes)?;
+        let len = u32::from_be_bytes(len_bytes) as usize;
+
+        if len > *plasma_constants::NUMBER_OF_TRANSACTIONS as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "more transactions than the circuit's batch size"));
+        }
+
+        let mut witnesses = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut tag = [0u8; 1];
+            reader.read_exact(&mut tag)?;
+            if tag[0] == 0 {
+                witnesses.push(None);
+                continue;
+            }
+
+            let tx = read_transaction(&mut reader, params)?;
+            let witness = read_transaction_witness(&mut reader)?;
+            witnesses.push(Some((tx, witness)));
+        }
+
+        Ok(Self {
+            old_root,
+            new_root,
+            public_data_commitment,
+            block_number,
+            total_fee,
+            witnesses,
+        })
+    }
+}
+
 type BabyProof = Proof<Bn256>;
 type BabyParameters = Parameters<Bn256>;
 
@@ -79,23 +411,51 @@ fn field_element_to_u32<P: PrimeField>(fr: P) -> u32 {
     res
 }
 
+fn write_fq_be(el: Fq, dest: &mut Vec<u8>) -> Result<(), BabyProverErr> {
+    el.into_repr().write_be(dest).map_err(BabyProverErr::IoError)
+}
+
+fn encode_g1_point(point: &G1Affine) -> Result<Vec<u8>, BabyProverErr> {
+    let (x, y) = point.into_xy_unchecked();
+    let mut buf = vec![];
+    write_fq_be(x, &mut buf)?;
+    write_fq_be(y, &mut buf)?;
+    Ok(buf)
+}
+
+fn encode_g2_point(point: &G2Affine) -> Result<Vec<u8>, BabyProverErr> {
+    let (x, y) = point.into_xy_unchecked();
+    let mut buf = vec![];
+    // Ethereum's ecPairing precompile expects the c1 (imaginary) coordinate before c0
+    write_fq_be(x.c1, &mut buf)?;
+    write_fq_be(x.c0, &mut buf)?;
+    write_fq_be(y.c1, &mut buf)?;
+    write_fq_be(y.c0, &mut buf)?;
+    Ok(buf)
+}
+
 // impl<'a> LifetimedProver<'a, Bn256> for BabyProver {
 //     fn create(initial_state: &'a State<E>) -> Option<Self> {
         
 //     }
 // }
 
-impl<'b> Prover<Bn256> for BabyProver {
-
-    type Err = BabyProverErr;
-    type Proof = BabyProof;
+impl BabyProver {
+    // Reserved account whose balance is never touched; padding transactions are self-transfers
+    // of zero amount and fee out of this account, so closing a short block leaves the Merkle
+    // path and leaf for this slot exactly as they were.
+    const PADDING_ACCOUNT_ID: u32 = 0;
 
-    fn new(initial_state: &State<Bn256>) 
-        -> Result<Self, Self::Err> 
+    pub fn with_batch_size(initial_state: &State<Bn256>, batch_size: usize)
+        -> Result<Self, BabyProverErr>
     {
         use std::fs::File;
         use std::io::{BufReader};
 
+        if batch_size != *plasma_constants::NUMBER_OF_TRANSACTIONS as usize {
+            return Err(BabyProverErr::Unknown);
+        }
+
         println!("Reading proving key, may take a while");
 
         let f_r = File::open("pk.key");
@@ -129,20 +489,97 @@ impl<'b> Prover<Bn256> for BabyProver {
             return Err(BabyProverErr::Unknown);
         }
 
+        // Any block shorter than `batch_size` gets padded from this reserved account, so its
+        // presence is a precondition for the prover as a whole, not just for a particular block.
+        if !tree.items.contains_key(&Self::PADDING_ACCOUNT_ID) {
+            return Err(BabyProverErr::MissingPaddingAccount);
+        }
+
         Ok(Self{
-            batch_size: 128,
+            batch_size,
             accounts_tree: tree,
             parameters: circuit_params.unwrap()
         })
     }
 
-    fn encode_proof(block: &Self::Proof) -> Result<Vec<u8>, Self::Err> {
+    // A block with more transactions than `batch_size` can't be padded into the circuit's
+    // fixed-size batch at all, so this is checked up front in `apply` before anything is mutated.
+    // Split out as its own function so the check can be exercised without a trusted-setup
+    // `Parameters` instance.
+    fn check_batch_size(batch_size: usize, num_txes: usize) -> Result<(), BabyProverErr> {
+        if num_txes > batch_size {
+            return Err(BabyProverErr::BatchOverflow);
+        }
+        Ok(())
+    }
+
+    // A self-transfer of zero amount and fee out of the reserved padding account. Leaves
+    // `accounts_tree` untouched, so several of these can be appended to a short block to bring
+    // it up to `batch_size` slots without perturbing `old_root`/`new_root`. `with_batch_size`
+    // already checked that the reserved account exists, but `accounts_tree` is mutable for the
+    // lifetime of `self`, so this re-checks rather than trusting that invariant forever. Takes
+    // `accounts_tree` directly, rather than `&self`, so it can be exercised without a
+    // trusted-setup `Parameters` instance.
+    fn padding_witness(accounts_tree: &balance_tree::BabyBalanceTree) -> Result<Option<(Transaction<Bn256>, TransactionWitness<Bn256>)>, BabyProverErr> {
+        let leaf = accounts_tree.items.get(&Self::PADDING_ACCOUNT_ID)
+            .ok_or(BabyProverErr::MissingPaddingAccount)?
+            .clone();
+
+        let path: Vec<Option<(Fr, bool)>> = accounts_tree.merkle_path(Self::PADDING_ACCOUNT_ID)
+            .into_iter().map(Some).collect();
+
+        let account_id = Fr::from_str(&Self::PADDING_ACCOUNT_ID.to_string()).unwrap();
+
+        let transaction = Transaction {
+            from: Some(account_id),
+            to: Some(account_id),
+            amount: Some(Fr::zero()),
+            fee: Some(Fr::zero()),
+            nonce: Some(leaf.nonce),
+            good_until_block: Some(Fr::zero()),
+            signature: None,
+        };
+
+        let transaction_witness = TransactionWitness {
+            auth_path_from: path.clone(),
+            balance_from: Some(leaf.balance),
+            nonce_from: Some(leaf.nonce),
+            pub_x_from: Some(leaf.pub_x),
+            pub_y_from: Some(leaf.pub_y),
+            auth_path_to: path,
+            balance_to: Some(leaf.balance),
+            nonce_to: Some(leaf.nonce),
+            pub_x_to: Some(leaf.pub_x),
+            pub_y_to: Some(leaf.pub_y),
+        };
+
+        Ok(Some((transaction, transaction_witness)))
+    }
+}
+
+impl<'b> Prover<Bn256> for BabyProver {
+
+    type Err = BabyProverErr;
+    type Proof = BabyProof;
+
+    fn new(initial_state: &State<Bn256>)
+        -> Result<Self, Self::Err>
+    {
+        Self::with_batch_size(initial_state, *plasma_constants::NUMBER_OF_TRANSACTIONS as usize)
+    }
+
+    fn encode_proof(proof: &Self::Proof) -> Result<Vec<u8>, Self::Err> {
 
         // uint256[8] memory in_proof
         // see contracts/Verifier.sol:44
 
-        // TODO: implement
-        unimplemented!()        
+        let mut encoding: Vec<u8> = Vec::with_capacity(8 * 32);
+
+        encoding.extend(encode_g1_point(&proof.a)?);
+        encoding.extend(encode_g2_point(&proof.b)?);
+        encoding.extend(encode_g1_point(&proof.c)?);
+
+        Ok(encoding)
     }
 
 
@@ -161,15 +598,23 @@ impl<'b> Prover<Bn256> for BabyProver {
 
     // Apply transactions to the state while also making a witness for proof, then calculate proof
     fn apply_and_prove(&mut self, block: &Block<Bn256>) -> Result<Self::Proof, Self::Err> {
+        let bundle = self.apply(block)?;
+        Self::prove_bundle(&bundle, &self.parameters)
+    }
+
+}
+
+impl BabyProver {
+    // Mutates accounts_tree and builds the witness bundle a proof needs. Reads evolving Merkle
+    // paths, so unlike `prove_bundle` this must run sequentially against `self`.
+    pub fn apply(&mut self, block: &Block<Bn256>) -> Result<BlockWitness, BabyProverErr> {
         let block_number = block.block_number;
         let public_data: Vec<u8> = BabyProver::encode_transactions(block).unwrap();
 
         let transactions = &block.transactions;
         let num_txes = transactions.len();
 
-        if num_txes != self.batch_size {
-            return Err(BabyProverErr::Unknown);
-        }
+        Self::check_batch_size(self.batch_size, num_txes)?;
 
         let mut witnesses: Vec<Option<(Transaction<Bn256>, TransactionWitness<Bn256>)>> = vec![];
 
@@ -177,6 +622,8 @@ impl<'b> Prover<Bn256> for BabyProver {
 
         let initial_root = self.accounts_tree.root_hash();
 
+        let jubjub_params = &AltJubjubBn256::new();
+
         for tx in transactions {
             let sender_leaf_number = field_element_to_u32(tx.from);
             let recipient_leaf_number = field_element_to_u32(tx.to);
@@ -188,14 +635,29 @@ impl<'b> Prover<Bn256> for BabyProver {
             if sender_leaf.is_none() || recipient_leaf.is_none() {
                 return Err(BabyProverErr::InvalidSender);
             }
-            
-            let parsed_transfer_amount = parse_float_to_u128(BitIterator::new(tx.amount.into_repr()).collect(), 
+
+            let unverified = UnverifiedTransaction::new(
+                tx.from.clone(),
+                tx.to.clone(),
+                tx.amount.clone(),
+                tx.fee.clone(),
+                tx.nonce.clone(),
+                tx.good_until_block.clone(),
+                tx.signature.clone(),
+                sender_leaf.unwrap().pub_x,
+                sender_leaf.unwrap().pub_y,
+                block_number,
+            );
+
+            let verified_tx = unverified.verify(jubjub_params)?;
+
+            let parsed_transfer_amount = parse_float_to_u128(BitIterator::new(verified_tx.amount.into_repr()).collect(),
                 *plasma_constants::AMOUNT_EXPONENT_BIT_WIDTH,
                 *plasma_constants::AMOUNT_MANTISSA_BIT_WIDTH,
                 10
             );
 
-            let parsed_fee = parse_float_to_u128(BitIterator::new(tx.fee.into_repr()).collect(), 
+            let parsed_fee = parse_float_to_u128(BitIterator::new(verified_tx.fee.into_repr()).collect(),
                 *plasma_constants::FEE_EXPONENT_BIT_WIDTH,
                 *plasma_constants::FEE_MANTISSA_BIT_WIDTH,
                 10
@@ -212,13 +674,13 @@ impl<'b> Prover<Bn256> for BabyProver {
             let path_to: Vec<Option<(Fr, bool)>> = self.accounts_tree.merkle_path(recipient_leaf_number).into_iter().map(|e| Some(e)).collect();
 
             let mut transaction : Transaction<Bn256> = Transaction {
-                from: Some(tx.from.clone()),
-                to: Some(tx.to.clone()),
-                amount: Some(tx.amount.clone()),
-                fee: Some(tx.fee.clone()),
-                nonce: Some(tx.nonce.clone()),
-                good_until_block: Some(tx.good_until_block.clone()),
-                signature: Some(tx.signature.clone())
+                from: Some(verified_tx.from.clone()),
+                to: Some(verified_tx.to.clone()),
+                amount: Some(verified_tx.amount.clone()),
+                fee: Some(verified_tx.fee.clone()),
+                nonce: Some(verified_tx.nonce.clone()),
+                good_until_block: Some(verified_tx.good_until_block.clone()),
+                signature: Some(verified_tx.signature.clone())
             };
 
             let mut updated_sender_leaf = sender_leaf.unwrap().clone();
@@ -263,6 +725,12 @@ impl<'b> Prover<Bn256> for BabyProver {
             }
         }
 
+        // Pad a short block out to batch_size with no-op witnesses so the circuit, which always
+        // expects exactly batch_size slots, still sees a full batch.
+        for _ in num_txes..self.batch_size {
+            witnesses.push(Self::padding_witness(&self.accounts_tree)?);
+        }
+
         let block_number = Fr::from_str(&block_number.to_string()).unwrap();
 
         let final_root = self.accounts_tree.root_hash();
@@ -317,34 +785,510 @@ impl<'b> Prover<Bn256> for BabyProver {
 
         let public_data_commitment = Fr::from_repr(repr).unwrap();
 
-        let params = &AltJubjubBn256::new();
+        Ok(BlockWitness {
+            old_root: initial_root,
+            new_root: final_root,
+            public_data_commitment,
+            block_number,
+            total_fee: total_fees,
+            witnesses,
+        })
+    }
+
+    // Stateless given its witness bundle, so this can run on a worker thread, or on a separate,
+    // GPU-equipped proving machine that never has to replay or hold the full balance tree.
+    pub fn prove_bundle(bundle: &BlockWitness, parameters: &BabyParameters) -> Result<BabyProof, BabyProverErr> {
+        let jubjub_params = &AltJubjubBn256::new();
 
         let instance = Update {
-            params: params,
-            number_of_transactions: num_txes,
-            old_root: Some(initial_root),
-            new_root: Some(final_root),
-            public_data_commitment: Some(public_data_commitment),
-            block_number: Some(block_number),
-            total_fee: Some(total_fees),
-            transactions: witnesses.clone(),
+            params: jubjub_params,
+            number_of_transactions: bundle.witnesses.len(),
+            old_root: Some(bundle.old_root),
+            new_root: Some(bundle.new_root),
+            public_data_commitment: Some(bundle.public_data_commitment),
+            block_number: Some(bundle.block_number),
+            total_fee: Some(bundle.total_fee),
+            transactions: bundle.witnesses.clone(),
         };
 
         let mut rng = OsRng::new().unwrap();
 
-        let proof = create_random_proof(instance, &self.parameters, & mut rng);
+        let proof = create_random_proof(instance, parameters, &mut rng);
         if proof.is_err() {
             return Err(BabyProverErr::Unknown);
         }
 
-        let pvk = prepare_verifying_key(&self.parameters.vk);
+        let pvk = prepare_verifying_key(&parameters.vk);
 
-        let success = verify_proof(&pvk, &proof.unwrap(), &[initial_root, final_root, public_data_commitment]).unwrap();
+        let success = verify_proof(&pvk, &proof.unwrap(), &[bundle.old_root, bundle.new_root, bundle.public_data_commitment]).unwrap();
         if !success {
             return Err(BabyProverErr::Unknown);
         }
 
         Ok(proof.unwrap())
     }
-    
+
+    // Packs alpha_g1, beta_g2, gamma_g2, delta_g2 and the gamma_abc_g1 (IC) points of the
+    // verifying key using the same fixed-width BE Fq layout as `encode_proof`, so the
+    // Verifier.sol deployment constants can be regenerated directly from a `VerifyingKey`
+    // instead of by hand. Takes `vk` directly, rather than `&self`, so the byte layout can be
+    // tested against known points without a trusted-setup `Parameters` instance.
+    pub fn encode_verification_key(vk: &VerifyingKey<Bn256>) -> Result<Vec<u8>, BabyProverErr> {
+        let mut encoding: Vec<u8> = vec![];
+
+        encoding.extend(encode_g1_point(&vk.alpha_g1)?);
+        encoding.extend(encode_g2_point(&vk.beta_g2)?);
+        encoding.extend(encode_g2_point(&vk.gamma_g2)?);
+        encoding.extend(encode_g2_point(&vk.delta_g2)?);
+
+        for ic in vk.ic.iter() {
+            encoding.extend(encode_g1_point(ic)?);
+        }
+
+        Ok(encoding)
+    }
+}
+
+// A future-proof handle for a proof being computed on a `BabyProverPool` worker.
+pub struct ProofHandle {
+    result: Receiver<Result<BabyProof, BabyProverErr>>,
+}
+
+impl ProofHandle {
+    // Non-blocking: `None` means the worker hasn't finished yet.
+    pub fn poll(&self) -> Option<Result<BabyProof, BabyProverErr>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(BabyProverErr::Unknown)),
+        }
+    }
+
+    // Blocks the calling thread until the worker finishes proving this block.
+    pub fn wait(self) -> Result<BabyProof, BabyProverErr> {
+        self.result.recv().unwrap_or(Err(BabyProverErr::Unknown))
+    }
+}
+
+type ProvingJob = Box<dyn FnOnce() + Send>;
+
+// Proves several already-applied blocks concurrently. `BabyParameters` is taken once in `new`
+// and shared via `Arc` across a fixed pool of worker threads sized to the machine's core count,
+// since `create_random_proof` is what dominates wall-clock time and is otherwise single-threaded.
+pub struct BabyProverPool {
+    parameters: Arc<BabyParameters>,
+    job_sender: Option<Sender<ProvingJob>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl BabyProverPool {
+    pub fn new(parameters: BabyParameters) -> Self {
+        let parameters = Arc::new(parameters);
+
+        let num_workers = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+
+        let (job_sender, job_receiver) = mpsc::channel::<ProvingJob>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..num_workers).map(|_| {
+            let job_receiver = Arc::clone(&job_receiver);
+            thread::spawn(move || {
+                loop {
+                    // Bind the result first so the lock is released before the job runs.
+                    let job = job_receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                }
+            })
+        }).collect();
+
+        Self { parameters, job_sender: Some(job_sender), workers }
+    }
+
+    // Dispatches the pure proving stage for an already-applied block to a worker. Several blocks
+    // can be in flight at once as long as each was produced by a sequential call to `apply`.
+    pub fn submit(&self, bundle: BlockWitness) -> ProofHandle {
+        let (result_sender, result_receiver) = mpsc::channel();
+        let parameters = Arc::clone(&self.parameters);
+
+        let job: ProvingJob = Box::new(move || {
+            let result = BabyProver::prove_bundle(&bundle, &parameters);
+            let _ = result_sender.send(result);
+        });
+
+        self.job_sender.as_ref()
+            .expect("job_sender is only taken in drop()")
+            .send(job)
+            .expect("proving pool workers should still be alive");
+
+        ProofHandle { result: result_receiver }
+    }
+}
+
+impl Drop for BabyProverPool {
+    fn drop(&mut self) {
+        // Drop the sender first so every worker's blocking `recv()` observes a disconnected
+        // channel and returns; otherwise `self.job_sender` (a live field, not yet dropped by the
+        // compiler's field-drop glue) would keep the channel open and the joins below would
+        // never return.
+        self.job_sender.take();
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sapling_crypto::eddsa::PrivateKey;
+
+    // Mirrors `UnverifiedTransaction::signed_message_bits` so tests can sign over exactly what
+    // `verify` checks against, without reaching into a private method from outside its impl.
+    fn transaction_signing_bytes(from: Fr, to: Fr, amount: Fr, fee: Fr, nonce: Fr, good_until_block: Fr) -> Vec<u8> {
+        let mut bits = vec![];
+        bits.extend(BitIterator::new(from.into_repr()));
+        bits.extend(BitIterator::new(to.into_repr()));
+        bits.extend(BitIterator::new(amount.into_repr()));
+        bits.extend(BitIterator::new(fee.into_repr()));
+        bits.extend(BitIterator::new(nonce.into_repr()));
+        bits.extend(BitIterator::new(good_until_block.into_repr()));
+        be_bit_vector_into_bytes(&bits)
+    }
+
+    #[test]
+    fn verify_accepts_a_correctly_signed_unexpired_transaction() {
+        let jubjub_params = AltJubjubBn256::new();
+        let mut rng = OsRng::new().unwrap();
+
+        let private_key = PrivateKey::<Bn256>(Fr::from_str("42").unwrap());
+        let public_key = PublicKey::from_private(&private_key, FixedGenerators::SpendingKeyGenerator, &jubjub_params);
+        let (pub_x, pub_y) = public_key.0.into_xy();
+
+        let from = Fr::from_str("1").unwrap();
+        let to = Fr::from_str("2").unwrap();
+        let amount = Fr::from_str("100").unwrap();
+        let fee = Fr::from_str("1").unwrap();
+        let nonce = Fr::from_str("0").unwrap();
+        let good_until_block = Fr::from_str("100").unwrap();
+
+        let message = transaction_signing_bytes(from, to, amount, fee, nonce, good_until_block);
+        let signature = private_key.sign_raw_message(
+            &message, &mut rng, FixedGenerators::SpendingKeyGenerator, &jubjub_params, message.len(),
+        );
+
+        let unverified = UnverifiedTransaction::new(
+            from, to, amount, fee, nonce, good_until_block, signature, pub_x, pub_y, 50,
+        );
+
+        assert!(unverified.verify(&jubjub_params).is_ok());
+    }
+
+    #[test]
+    fn verify_rejects_a_transaction_whose_fields_were_tampered_with_after_signing() {
+        let jubjub_params = AltJubjubBn256::new();
+        let mut rng = OsRng::new().unwrap();
+
+        let private_key = PrivateKey::<Bn256>(Fr::from_str("42").unwrap());
+        let public_key = PublicKey::from_private(&private_key, FixedGenerators::SpendingKeyGenerator, &jubjub_params);
+        let (pub_x, pub_y) = public_key.0.into_xy();
+
+        let from = Fr::from_str("1").unwrap();
+        let to = Fr::from_str("2").unwrap();
+        let amount = Fr::from_str("100").unwrap();
+        let fee = Fr::from_str("1").unwrap();
+        let nonce = Fr::from_str("0").unwrap();
+        let good_until_block = Fr::from_str("100").unwrap();
+
+        let message = transaction_signing_bytes(from, to, amount, fee, nonce, good_until_block);
+        let signature = private_key.sign_raw_message(
+            &message, &mut rng, FixedGenerators::SpendingKeyGenerator, &jubjub_params, message.len(),
+        );
+
+        // The recipient's signed amount is bumped after the signature was produced over the
+        // original value, the way a malicious relayer tampering with a forwarded transaction would.
+        let tampered_amount = Fr::from_str("999999").unwrap();
+        let unverified = UnverifiedTransaction::new(
+            from, to, tampered_amount, fee, nonce, good_until_block, signature, pub_x, pub_y, 50,
+        );
+
+        match unverified.verify(&jubjub_params) {
+            Err(BabyProverErr::InvalidSignature) => {},
+            other => panic!("expected InvalidSignature, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn verify_rejects_a_transaction_whose_good_until_block_has_passed() {
+        let jubjub_params = AltJubjubBn256::new();
+        let mut rng = OsRng::new().unwrap();
+
+        let private_key = PrivateKey::<Bn256>(Fr::from_str("42").unwrap());
+        let public_key = PublicKey::from_private(&private_key, FixedGenerators::SpendingKeyGenerator, &jubjub_params);
+        let (pub_x, pub_y) = public_key.0.into_xy();
+
+        let from = Fr::from_str("1").unwrap();
+        let to = Fr::from_str("2").unwrap();
+        let amount = Fr::from_str("100").unwrap();
+        let fee = Fr::from_str("1").unwrap();
+        let nonce = Fr::from_str("0").unwrap();
+        let good_until_block = Fr::from_str("10").unwrap();
+
+        let message = transaction_signing_bytes(from, to, amount, fee, nonce, good_until_block);
+        let signature = private_key.sign_raw_message(
+            &message, &mut rng, FixedGenerators::SpendingKeyGenerator, &jubjub_params, message.len(),
+        );
+
+        // block_number (50) is already past good_until_block (10), even though the signature
+        // itself is perfectly valid.
+        let unverified = UnverifiedTransaction::new(
+            from, to, amount, fee, nonce, good_until_block, signature, pub_x, pub_y, 50,
+        );
+
+        match unverified.verify(&jubjub_params) {
+            Err(BabyProverErr::TransactionExpired) => {},
+            other => panic!("expected TransactionExpired, got {:?}", other),
+        }
+    }
+
+    // Builds a bundle with a mix of present and absent witness slots, non-trivial auth paths and
+    // a real EdDSA signature, then checks that `read(write(bundle))` reproduces the bundle
+    // byte-for-byte. This is the wire format shipped to an untrusted, external proving machine,
+    // so a silent mismatch here would be a correctness bug, not just a style nit.
+    #[test]
+    fn block_witness_round_trips_through_write_and_read() {
+        let jubjub_params = AltJubjubBn256::new();
+        let mut rng = OsRng::new().unwrap();
+
+        let private_key = PrivateKey::<Bn256>(Fr::from_str("12345").unwrap());
+        let public_key = PublicKey::from_private(&private_key, FixedGenerators::SpendingKeyGenerator, &jubjub_params);
+        let (pub_x, pub_y) = public_key.0.into_xy();
+
+        let message = vec![1u8, 2, 3, 4, 5];
+        let signature = private_key.sign_raw_message(
+            &message,
+            &mut rng,
+            FixedGenerators::SpendingKeyGenerator,
+            &jubjub_params,
+            message.len(),
+        );
+
+        let auth_path = vec![
+            Some((Fr::from_str("7").unwrap(), false)),
+            None,
+            Some((Fr::from_str("9").unwrap(), true)),
+        ];
+
+        let full_tx = Transaction {
+            from: Some(Fr::from_str("1").unwrap()),
+            to: Some(Fr::from_str("2").unwrap()),
+            amount: Some(Fr::from_str("1000").unwrap()),
+            fee: Some(Fr::from_str("1").unwrap()),
+            nonce: Some(Fr::from_str("0").unwrap()),
+            good_until_block: Some(Fr::from_str("100").unwrap()),
+            signature: Some(signature),
+        };
+
+        let full_witness = TransactionWitness {
+            auth_path_from: auth_path.clone(),
+            balance_from: Some(Fr::from_str("5000").unwrap()),
+            nonce_from: Some(Fr::from_str("0").unwrap()),
+            pub_x_from: Some(pub_x),
+            pub_y_from: Some(pub_y),
+            auth_path_to: auth_path,
+            balance_to: Some(Fr::from_str("3000").unwrap()),
+            nonce_to: Some(Fr::from_str("4").unwrap()),
+            pub_x_to: Some(pub_x),
+            pub_y_to: Some(pub_y),
+        };
+
+        let sparse_tx = Transaction {
+            from: Some(Fr::from_str("3").unwrap()),
+            to: Some(Fr::from_str("3").unwrap()),
+            amount: Some(Fr::zero()),
+            fee: Some(Fr::zero()),
+            nonce: Some(Fr::from_str("1").unwrap()),
+            good_until_block: Some(Fr::zero()),
+            signature: None,
+        };
+
+        let sparse_witness = TransactionWitness {
+            auth_path_from: vec![None, None],
+            balance_from: Some(Fr::zero()),
+            nonce_from: Some(Fr::from_str("1").unwrap()),
+            pub_x_from: None,
+            pub_y_from: None,
+            auth_path_to: vec![None, None],
+            balance_to: Some(Fr::zero()),
+            nonce_to: Some(Fr::from_str("1").unwrap()),
+            pub_x_to: None,
+            pub_y_to: None,
+        };
+
+        let bundle = BlockWitness {
+            old_root: Fr::from_str("11").unwrap(),
+            new_root: Fr::from_str("12").unwrap(),
+            public_data_commitment: Fr::from_str("13").unwrap(),
+            block_number: Fr::from_str("1").unwrap(),
+            total_fee: Fr::from_str("1").unwrap(),
+            witnesses: vec![
+                Some((full_tx, full_witness)),
+                None,
+                Some((sparse_tx, sparse_witness)),
+            ],
+        };
+
+        let mut encoded = vec![];
+        bundle.write(&mut encoded).expect("writing a well-formed bundle must not fail");
+
+        let decoded = BlockWitness::read(&encoded[..], &jubjub_params)
+            .expect("reading back what we just wrote must not fail");
+
+        assert_eq!(decoded.old_root, bundle.old_root);
+        assert_eq!(decoded.new_root, bundle.new_root);
+        assert_eq!(decoded.public_data_commitment, bundle.public_data_commitment);
+        assert_eq!(decoded.block_number, bundle.block_number);
+        assert_eq!(decoded.total_fee, bundle.total_fee);
+        assert_eq!(decoded.witnesses.len(), bundle.witnesses.len());
+
+        let mut re_encoded = vec![];
+        decoded.write(&mut re_encoded).expect("re-encoding the decoded bundle must not fail");
+        assert_eq!(re_encoded, encoded, "round trip through write/read must be byte-exact");
+    }
+
+    #[test]
+    fn check_batch_size_accepts_blocks_at_or_under_capacity() {
+        assert!(BabyProver::check_batch_size(4, 0).is_ok());
+        assert!(BabyProver::check_batch_size(4, 4).is_ok());
+    }
+
+    #[test]
+    fn check_batch_size_rejects_blocks_over_capacity() {
+        match BabyProver::check_batch_size(4, 5) {
+            Err(BabyProverErr::BatchOverflow) => {},
+            other => panic!("expected BatchOverflow, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn padding_witness_is_a_zero_value_self_transfer_from_the_reserved_account() {
+        let mut tree = balance_tree::BabyBalanceTree::new(*plasma_constants::BALANCE_TREE_DEPTH as u32);
+        tree.insert(BabyProver::PADDING_ACCOUNT_ID, balance_tree::Leaf {
+            balance: Fr::from_str("500").unwrap(),
+            nonce: Fr::from_str("3").unwrap(),
+            pub_x: Fr::zero(),
+            pub_y: Fr::zero(),
+        });
+
+        let (tx, witness) = BabyProver::padding_witness(&tree)
+            .expect("padding account is present")
+            .expect("padding_witness always produces a witness when the account is present");
+
+        assert_eq!(tx.amount, Some(Fr::zero()));
+        assert_eq!(tx.fee, Some(Fr::zero()));
+        assert_eq!(tx.from, tx.to);
+        assert_eq!(witness.balance_from, Some(Fr::from_str("500").unwrap()));
+        assert_eq!(witness.balance_to, Some(Fr::from_str("500").unwrap()));
+    }
+
+    #[test]
+    fn padding_witness_fails_instead_of_panicking_when_the_reserved_account_is_missing() {
+        let tree = balance_tree::BabyBalanceTree::new(*plasma_constants::BALANCE_TREE_DEPTH as u32);
+
+        match BabyProver::padding_witness(&tree) {
+            Err(BabyProverErr::MissingPaddingAccount) => {},
+            other => panic!("expected MissingPaddingAccount, got {:?}", other),
+        }
+    }
+
+    // Regression test for the `Drop` deadlock: the pool used to join its workers while still
+    // holding `job_sender` open, so the workers' `recv()` never returned. Runs the drop on its
+    // own thread and fails (rather than hanging the whole test binary) if it doesn't finish
+    // promptly.
+    #[test]
+    fn dropping_prover_pool_does_not_hang() {
+        use bellman::{Circuit, ConstraintSystem, SynthesisError};
+        use bellman::groth16::generate_random_parameters;
+        use std::time::Duration;
+
+        // Stands in for the real `Update` circuit: the pool's Drop behavior doesn't depend on
+        // what the parameters were generated for, only on having a valid `Parameters<Bn256>`.
+        struct DummyCircuit;
+        impl Circuit<Bn256> for DummyCircuit {
+            fn synthesize<CS: ConstraintSystem<Bn256>>(self, cs: &mut CS) -> Result<(), SynthesisError> {
+                let a = cs.alloc(|| "a", || Ok(Fr::one()))?;
+                let b = cs.alloc_input(|| "b", || Ok(Fr::one()))?;
+                cs.enforce(|| "a = b", |lc| lc + a, |lc| lc + CS::one(), |lc| lc + b);
+                Ok(())
+            }
+        }
+
+        let mut rng = OsRng::new().unwrap();
+        let parameters = generate_random_parameters::<Bn256, _, _>(DummyCircuit, &mut rng)
+            .expect("trivial circuit should generate parameters");
+
+        let pool = BabyProverPool::new(parameters);
+
+        let (done_sender, done_receiver) = mpsc::channel();
+        thread::spawn(move || {
+            drop(pool);
+            let _ = done_sender.send(());
+        });
+
+        done_receiver.recv_timeout(Duration::from_secs(5))
+            .expect("dropping BabyProverPool must not deadlock");
+    }
+
+    // `Verifier.sol` parses this layout directly, so a silently-swapped coordinate or misordered
+    // field would break on-chain verification rather than fail loudly.
+    #[test]
+    fn encode_proof_packs_a_b_c_as_fixed_width_be_fq_coordinates() {
+        let proof = Proof::<Bn256> {
+            a: G1Affine::one(),
+            b: G2Affine::one(),
+            c: G1Affine::one(),
+        };
+
+        let encoded = BabyProver::encode_proof(&proof).expect("encoding a valid proof must not fail");
+
+        let mut expected = vec![];
+        expected.extend(encode_g1_point(&proof.a).unwrap());
+        expected.extend(encode_g2_point(&proof.b).unwrap());
+        expected.extend(encode_g1_point(&proof.c).unwrap());
+
+        assert_eq!(encoded.len(), 8 * 32);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn encode_verification_key_packs_alpha_beta_gamma_delta_and_ic_in_order() {
+        let vk = VerifyingKey::<Bn256> {
+            alpha_g1: G1Affine::one(),
+            beta_g1: G1Affine::one(),
+            beta_g2: G2Affine::one(),
+            gamma_g2: G2Affine::one(),
+            delta_g1: G1Affine::one(),
+            delta_g2: G2Affine::one(),
+            ic: vec![G1Affine::one(), G1Affine::one()],
+        };
+
+        let encoded = BabyProver::encode_verification_key(&vk).expect("encoding a valid vk must not fail");
+
+        let mut expected = vec![];
+        expected.extend(encode_g1_point(&vk.alpha_g1).unwrap());
+        expected.extend(encode_g2_point(&vk.beta_g2).unwrap());
+        expected.extend(encode_g2_point(&vk.gamma_g2).unwrap());
+        expected.extend(encode_g2_point(&vk.delta_g2).unwrap());
+        for ic in &vk.ic {
+            expected.extend(encode_g1_point(ic).unwrap());
+        }
+
+        // 1 G1 point (alpha_g1) + 3 G2 points (beta_g2, gamma_g2, delta_g2; beta_g1 and delta_g1
+        // aren't encoded) + one G1 point per IC entry.
+        assert_eq!(encoded.len(), 2 * 32 + 3 * 4 * 32 + vk.ic.len() * 2 * 32);
+        assert_eq!(encoded, expected);
+    }
 }
\ No newline at end of file